@@ -1,8 +1,12 @@
-use std::{error::Error, path::PathBuf};
+use std::{error::Error, fs, io, path::PathBuf};
 
-use clap::{Args, CommandFactory, Parser, Subcommand};
+use clap::{ArgAction, Args, CommandFactory, Parser, Subcommand, ValueEnum};
 
-use crate::{error::CliError, store::SshKeyStorage};
+use crate::{
+    config,
+    error::CliError,
+    store::{DEFAULT_RSA_BITS, Key, KeyType, SshKeyStorage},
+};
 
 pub const BIN_NAME: &str = env!("CARGO_BIN_NAME");
 
@@ -15,6 +19,24 @@ pub const BIN_NAME: &str = env!("CARGO_BIN_NAME");
 pub struct KeyManCli {
     #[command(subcommand)]
     pub subcommand: Option<Command>,
+
+    #[arg(
+        short,
+        long,
+        global = true,
+        action = ArgAction::Count,
+        help = "Increase log verbosity, repeatable (-v, -vv)"
+    )]
+    pub verbose: u8,
+
+    #[arg(
+        short,
+        long,
+        global = true,
+        conflicts_with = "verbose",
+        help = "Suppress all output except errors"
+    )]
+    pub quiet: bool,
 }
 
 #[derive(Debug, Clone, Args)]
@@ -39,18 +61,184 @@ pub struct AddArgs {
         help = "Immediately place this key in use after adding it"
     )]
     use_key: bool,
+
+    #[arg(
+        long,
+        help = "Passphrase to decrypt the private key with when deriving its public key"
+    )]
+    passphrase: Option<String>,
 }
 
 #[derive(Debug, Clone, Args)]
-pub struct RenameArgs {
+pub struct GenerateArgs {
+    #[arg(
+        short = 't',
+        long = "type",
+        value_enum,
+        help = "The algorithm to generate the keypair with, default is the `default-key-algorithm` config field"
+    )]
+    key_type: Option<KeyType>,
+
+    #[arg(
+        long,
+        default_value_t = DEFAULT_RSA_BITS,
+        help = "Number of bits to use, only applies to --type rsa"
+    )]
+    bits: usize,
+
+    #[arg(
+        short,
+        long,
+        alias = "save-as",
+        help = "A name to identify the key by, default will be `key<n>`"
+    )]
+    name: Option<String>,
+
+    #[arg(long, help = "Comment to embed in the public key, default is `user@host`")]
+    comment: Option<String>,
+
+    #[arg(long, help = "Encrypt the private key on disk with a passphrase")]
+    passphrase: Option<String>,
+
+    #[arg(
+        short,
+        long,
+        help = "Immediately place this key in use after generating it"
+    )]
+    use_key: bool,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct RenewArgs {
     key_name: String,
+
+    #[arg(
+        long,
+        help = "Passphrase to decrypt the old private key and re-encrypt the new one with"
+    )]
+    passphrase: Option<String>,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct RenameArgs {
     new_name: String,
+
+    #[arg(
+        short,
+        long = "key",
+        help = "The key to rename, picked interactively if omitted"
+    )]
+    key_name: Option<String>,
+}
+
+/// A parsed `user@hostname[:port]` CLI argument for `host add`.
+#[derive(Debug, Clone)]
+pub struct HostTarget {
+    user: String,
+    hostname: String,
+    port: Option<u16>,
+}
+
+impl std::str::FromStr for HostTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (user, rest) = s
+            .split_once('@')
+            .ok_or_else(|| "expected format <user@hostname[:port]>".to_string())?;
+
+        let (hostname, port) = match rest.split_once(':') {
+            Some((hostname, port)) => (
+                hostname,
+                Some(
+                    port.parse::<u16>()
+                        .map_err(|_| format!("invalid port '{port}'"))?,
+                ),
+            ),
+            None => (rest, None),
+        };
+
+        if user.is_empty() || hostname.is_empty() {
+            return Err("expected format <user@hostname[:port]>".to_string());
+        }
+
+        Ok(HostTarget {
+            user: user.to_string(),
+            hostname: hostname.to_string(),
+            port,
+        })
+    }
 }
 
 #[derive(Debug, Clone, Args)]
-pub struct RemoveArgs {
+pub struct HostAddArgs {
     key_name: String,
 
+    #[arg(value_name = "USER@HOSTNAME[:PORT]")]
+    target: HostTarget,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct HostRemoveArgs {
+    key_name: String,
+    host_id: String,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum HostCommand {
+    #[command(
+        name = "add",
+        about = "Associate a key with a Host entry in ~/.ssh/config"
+    )]
+    Add(HostAddArgs),
+
+    #[command(
+        name = "rm",
+        alias = "remove",
+        about = "Remove a key's association with a host"
+    )]
+    Rm(HostRemoveArgs),
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct HostArgs {
+    #[command(subcommand)]
+    command: HostCommand,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct ConfigGetArgs {
+    #[arg(help = "Field name, e.g. default-key-algorithm, default-identity-file, ssh-dir, auto-derive-public-key")]
+    field: String,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct ConfigSetArgs {
+    #[arg(help = "Field name, e.g. default-key-algorithm, default-identity-file, ssh-dir, auto-derive-public-key")]
+    field: String,
+
+    value: String,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum ConfigCommand {
+    #[command(name = "get", about = "Print a config field's current value")]
+    Get(ConfigGetArgs),
+
+    #[command(name = "set", about = "Persist a config field to config.toml")]
+    Set(ConfigSetArgs),
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct ConfigArgs {
+    #[command(subcommand)]
+    command: ConfigCommand,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct RemoveArgs {
+    key_name: Option<String>,
+
     #[arg(
         short,
         long,
@@ -69,19 +257,31 @@ pub enum Command {
     )]
     Add(AddArgs),
 
+    #[command(
+        name = "generate",
+        alias = "gen",
+        about = "Generate a brand-new SSH keypair directly in the store"
+    )]
+    Generate(GenerateArgs),
+
     #[command(
         name = "use",
         alias = "swap",
         about = "Symlinks the related private/public key files into ~/.ssh folder"
     )]
-    Use { key_name: String },
+    Use { key_name: Option<String> },
 
     #[command(
         name = "info",
         alias = "show",
         about = "Show information about a key or the currently active key"
     )]
-    Info { key_name: Option<String> },
+    Info {
+        key_name: Option<String>,
+
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Plain, help = "Output format")]
+        format: OutputFormat,
+    },
 
     #[command(name = "rename", alias = "mv", about = "Rename a key to a new name")]
     Rename(RenameArgs),
@@ -89,12 +289,129 @@ pub enum Command {
     #[command(name = "remove", alias = "rm", about = "Remove a key by name")]
     Remove(RemoveArgs),
 
+    #[command(
+        name = "renew",
+        about = "Regenerate a key's keypair while preserving its name and algorithm"
+    )]
+    Renew(RenewArgs),
+
+    #[command(
+        name = "host",
+        about = "Manage per-host key associations in ~/.ssh/config"
+    )]
+    Host(HostArgs),
+
     #[command(name = "list", aliases = ["ls", "-l", "--list"], about = "List all keys")]
-    List, // todo: options for output formatting
+    List(ListArgs),
+
+    #[command(name = "config", about = "View or change keyman's default settings")]
+    Config(ConfigArgs),
+}
+
+/// Output format shared by `list` and `info`.
+#[derive(Debug, Clone, Copy, Default, clap::ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Plain,
+    Table,
+    Json,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct ListArgs {
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Plain, help = "Output format")]
+    format: OutputFormat,
 }
 
 pub type SubcommandResult = Result<(), CliError>;
 
+/// Prints `keys` as a column-aligned table of name, algorithm, fingerprint, in-use, and hosts.
+fn print_table(keys: &[&Key], current_key_name: Option<&str>) {
+    let headers = ["NAME", "ALGORITHM", "FINGERPRINT", "IN USE", "HOSTS"];
+
+    let rows: Vec<[String; 5]> = keys
+        .iter()
+        .map(|key| {
+            let in_use = Some(key.name.as_str()) == current_key_name;
+            let hosts = key
+                .hosts
+                .iter()
+                .map(|h| h.id.as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            [
+                key.name.clone(),
+                key.algorithm_name().unwrap_or_else(|| "-".to_string()),
+                key.fingerprint().unwrap_or_else(|| "-".to_string()),
+                (if in_use { "yes" } else { "no" }).to_string(),
+                if hosts.is_empty() { "-".to_string() } else { hosts },
+            ]
+        })
+        .collect();
+
+    let mut widths: [usize; 5] = headers.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let print_row = |cells: &[&str]| {
+        let line: Vec<String> = cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect();
+
+        println!("{}", line.join("  "));
+    };
+
+    print_row(&headers);
+    for row in &rows {
+        let cells: Vec<&str> = row.iter().map(String::as_str).collect();
+        print_row(&cells);
+    }
+}
+
+fn is_interactive() -> bool {
+    use std::io::IsTerminal;
+
+    io::stdin().is_terminal()
+}
+
+/// Opens a fuzzy-search selector over the stored keys, returning `None` when stdin isn't a TTY,
+/// there are no keys to pick from, or the user backs out of the prompt.
+fn pick_key_name(store: &SshKeyStorage, prompt: &str) -> Option<String> {
+    if !is_interactive() {
+        return None;
+    }
+
+    let mut names: Vec<String> = store.get_keys().iter().map(|k| k.name.clone()).collect();
+    names.sort();
+
+    if names.is_empty() {
+        return None;
+    }
+
+    let selection = dialoguer::FuzzySelect::new()
+        .with_prompt(prompt)
+        .items(&names)
+        .interact_opt()
+        .ok()
+        .flatten()?;
+
+    Some(names.remove(selection))
+}
+
+/// Resolves an explicit key name, falling back to the interactive picker, and errors out
+/// explicitly rather than silently looking up an empty string when neither is available.
+fn require_key_name(explicit: Option<String>, store: &SshKeyStorage, prompt: &str) -> Result<String, CliError> {
+    explicit
+        .or_else(|| pick_key_name(store, prompt))
+        .ok_or_else(|| CliError::Message("no key given and none was picked interactively".to_string()))
+}
+
 impl KeyManCli {
     pub fn usage_msg_from(&self, args: &[&str]) -> String {
         format!("{BIN_NAME} {}", args.join(" "))
@@ -102,10 +419,14 @@ impl KeyManCli {
 
     pub fn handle_add(&self, args: &AddArgs, store: &mut SshKeyStorage) -> SubcommandResult {
         let key = store
-            .add_key(args.private_key.clone(), args.name.as_deref())
+            .add_key(
+                args.private_key.clone(),
+                args.name.as_deref(),
+                args.passphrase.clone(),
+            )
             .map_err(|e| CliError::Message(e.to_string()))?;
 
-        println!(
+        log::info!(
             "Added key '{}' to list of keys, use it with `{}`",
             key.name,
             self.usage_msg_from(&["use", &key.name])
@@ -119,7 +440,47 @@ impl KeyManCli {
                 .use_key(&key_name)
                 .map_err(|e| CliError::Misc(e.into()))?;
 
-            println!("Now using '{}'", &key_name);
+            log::info!("Now using '{}'", &key_name);
+        }
+
+        store.save().map_err(CliError::SaveFailed)?;
+        Ok(())
+    }
+
+    pub fn handle_generate(
+        &self,
+        args: &GenerateArgs,
+        store: &mut SshKeyStorage,
+    ) -> SubcommandResult {
+        let key_type = args.key_type.unwrap_or_else(|| {
+            KeyType::from_str(&config::load().default_key_algorithm, true).unwrap_or(KeyType::Ed25519)
+        });
+
+        let key = store
+            .generate_key(
+                key_type,
+                args.bits,
+                args.name.as_deref(),
+                args.comment.clone(),
+                args.passphrase.as_deref(),
+            )
+            .map_err(|e| CliError::Message(e.to_string()))?;
+
+        log::info!(
+            "Generated key '{}', use it with `{}`",
+            key.name,
+            self.usage_msg_from(&["use", &key.name])
+        );
+
+        // clone so the previous borrow 'ends' and we can use store.use_key
+        let key_name = key.name.clone();
+
+        if args.use_key {
+            store
+                .use_key(&key_name)
+                .map_err(|e| CliError::Misc(e.into()))?;
+
+            log::info!("Now using '{}'", &key_name);
         }
 
         store.save().map_err(CliError::SaveFailed)?;
@@ -127,34 +488,57 @@ impl KeyManCli {
     }
 
     pub fn handle_rename(&self, args: &RenameArgs, store: &mut SshKeyStorage) -> SubcommandResult {
+        let key_name = require_key_name(args.key_name.clone(), store, "Select a key to rename")?;
+
         let key = store
-            .rename_key(&args.key_name, &args.new_name)
-            .ok_or(CliError::KeyNotFound(args.key_name.clone()))?;
+            .rename_key(&key_name, &args.new_name)
+            .ok_or(CliError::KeyNotFound(key_name.clone()))?;
 
-        println!("Renamed from '{}' -> '{}'", &args.key_name, key.name);
+        log::info!("Renamed from '{}' -> '{}'", &key_name, key.name);
         store.save().map_err(CliError::SaveFailed)?;
 
         Ok(())
     }
 
-    pub fn handle_list(&self, store: &SshKeyStorage) -> SubcommandResult {
+    pub fn handle_list(&self, store: &SshKeyStorage, format: OutputFormat) -> SubcommandResult {
         let current_key_name = store.get_active_key().map(|k| k.name.as_str());
 
-        println!("Your SSH keys:");
+        let mut keys: Vec<&Key> = store.get_keys();
+        keys.sort_by(|a, b| a.name.cmp(&b.name));
 
-        for &key in store.get_keys().iter() {
-            let in_use = Some(key.name.as_str()) == current_key_name;
+        match format {
+            OutputFormat::Plain => {
+                println!("Your SSH keys:");
+
+                for key in keys {
+                    let in_use = Some(key.name.as_str()) == current_key_name;
 
-            println!("  - {}{}", key.name, if in_use { " (in use)" } else { "" });
+                    println!("  - {}{}", key.name, if in_use { " (in use)" } else { "" });
+                }
+            }
+
+            OutputFormat::Table => print_table(&keys, current_key_name),
+
+            OutputFormat::Json => {
+                let views: Vec<_> = keys
+                    .iter()
+                    .map(|key| key.to_view(Some(key.name.as_str()) == current_key_name))
+                    .collect();
+
+                let json = serde_json::to_string_pretty(&views).map_err(|e| CliError::Misc(e.into()))?;
+                println!("{json}");
+            }
         }
 
         Ok(())
     }
 
-    pub fn handle_use(&self, key_name: &str, store: &mut SshKeyStorage) -> SubcommandResult {
-        match store.use_key(key_name) {
+    pub fn handle_use(&self, key_name: Option<&str>, store: &mut SshKeyStorage) -> SubcommandResult {
+        let key_name = require_key_name(key_name.map(ToString::to_string), store, "Select a key to use")?;
+
+        match store.use_key(&key_name) {
             Ok(Some(key)) => {
-                println!(
+                log::info!(
                     "Selected and now using key '{}', linked as SSH key",
                     key.name
                 );
@@ -163,18 +547,54 @@ impl KeyManCli {
                 Ok(())
             }
 
-            Ok(None) => Err(CliError::KeyNotFound(key_name.to_string())),
+            Ok(None) => Err(CliError::KeyNotFound(key_name)),
             Err(err) => Err(CliError::Misc(Box::new(err))),
         }
     }
 
-    pub fn handle_info(&self, key_name: Option<&str>, store: &SshKeyStorage) -> SubcommandResult {
+    pub fn handle_info(
+        &self,
+        key_name: Option<&str>,
+        store: &SshKeyStorage,
+        format: OutputFormat,
+    ) -> SubcommandResult {
+        let picked_name;
+        let key_name = match key_name {
+            Some(name) => Some(name),
+            None if store.get_active_key().is_none() => {
+                picked_name = pick_key_name(store, "Select a key to view");
+                picked_name.as_deref()
+            }
+            None => None,
+        };
+
         let key = key_name
             .and_then(|name| store.get_key(name))
             .or_else(|| store.get_active_key());
 
         match (key_name, key) {
             (_, Some(key)) => {
+                let in_use = store.get_active_key().is_some_and(|k| k.name == key.name);
+                self.print_key_info(key, in_use, format)
+            }
+
+            (Some(key_name), None) => Err(CliError::KeyNotFound(key_name.to_string())),
+
+            (None, None) => {
+                Self::command()
+                    .find_subcommand_mut("info")
+                    .expect("failed to find `info` subcommand")
+                    .print_help()
+                    .expect("failed to print help");
+
+                Ok(())
+            }
+        }
+    }
+
+    fn print_key_info(&self, key: &Key, in_use: bool, format: OutputFormat) -> SubcommandResult {
+        match format {
+            OutputFormat::Plain => {
                 println!("Viewing Key '{}':", &key.name);
 
                 if let Some(ref private_key_path) = key.private_key_path {
@@ -184,50 +604,163 @@ impl KeyManCli {
                 if let Some(ref public_key_path) = key.public_key_path {
                     println!("  Public Key: {}", public_key_path.to_string_lossy());
                 }
+            }
 
-                Ok(())
+            OutputFormat::Table => print_table(&[key], if in_use { Some(key.name.as_str()) } else { None }),
+
+            OutputFormat::Json => {
+                let json = serde_json::to_string_pretty(&key.to_view(in_use))
+                    .map_err(|e| CliError::Misc(e.into()))?;
+
+                println!("{json}");
             }
+        }
 
-            (Some(key_name), None) => Err(CliError::KeyNotFound(key_name.to_string())),
+        Ok(())
+    }
 
-            (None, None) => {
-                Self::command()
-                    .find_subcommand_mut("info")
-                    .expect("failed to find `info` subcommand")
-                    .print_help()
-                    .expect("failed to print help");
+    pub fn handle_host(&self, args: &HostArgs, store: &mut SshKeyStorage) -> SubcommandResult {
+        match &args.command {
+            HostCommand::Add(args) => self.handle_host_add(args, store),
+            HostCommand::Rm(args) => self.handle_host_remove(args, store),
+        }
+    }
+
+    pub fn handle_host_add(&self, args: &HostAddArgs, store: &mut SshKeyStorage) -> SubcommandResult {
+        let host = store
+            .add_host(
+                &args.key_name,
+                &args.target.user,
+                &args.target.hostname,
+                args.target.port,
+            )
+            .map_err(|e| CliError::Message(e.to_string()))?;
+
+        log::info!(
+            "Key '{}' will now be used for Host '{}' ({}@{}{})",
+            args.key_name,
+            host.id,
+            host.user,
+            host.hostname,
+            host.port.map_or(String::new(), |p| format!(":{p}")),
+        );
+
+        store.save().map_err(CliError::SaveFailed)?;
+        Ok(())
+    }
+
+    pub fn handle_host_remove(
+        &self,
+        args: &HostRemoveArgs,
+        store: &mut SshKeyStorage,
+    ) -> SubcommandResult {
+        match store.remove_host(&args.key_name, &args.host_id) {
+            Some(_) => {
+                store.save().map_err(CliError::SaveFailed)?;
+
+                log::info!(
+                    "Removed host '{}' from key '{}'",
+                    args.host_id, args.key_name
+                );
 
                 Ok(())
             }
+
+            None => Err(CliError::Message(format!(
+                "No host '{}' associated with key '{}'",
+                args.host_id, args.key_name
+            ))),
         }
     }
 
+    pub fn handle_renew(&self, args: &RenewArgs, store: &mut SshKeyStorage) -> SubcommandResult {
+        let key = store
+            .renew_key(&args.key_name, args.passphrase.clone())
+            .map_err(|e| CliError::Message(e.to_string()))?;
+
+        log::info!(
+            "Renewed key '{}', the old keypair was archived under keys/archive/",
+            key.name
+        );
+
+        if let Some(ref public_key_path) = key.public_key_path {
+            if let Ok(public_key) = fs::read_to_string(public_key_path) {
+                println!("Update your remote authorized_keys with the new public key:");
+                println!("{}", public_key.trim());
+            }
+        }
+
+        store.save().map_err(CliError::SaveFailed)?;
+        Ok(())
+    }
+
     pub fn handle_remove(&self, args: &RemoveArgs, store: &mut SshKeyStorage) -> SubcommandResult {
+        let key_name = require_key_name(args.key_name.clone(), store, "Select a key to remove")?;
+
         let active_key = store.get_active_key();
 
         if let Some(key) = active_key {
-            let in_use = key.name == *args.key_name;
+            let in_use = key.name == key_name;
 
             if in_use && !args.force {
-                return Err(CliError::Message(
-                    "Use --force to remove a key that is currently in use".to_string(),
-                ));
+                let confirmed = is_interactive()
+                    && dialoguer::Confirm::new()
+                        .with_prompt(format!(
+                            "'{key_name}' is currently in use, remove it anyway?"
+                        ))
+                        .default(false)
+                        .interact()
+                        .unwrap_or(false);
+
+                if !confirmed {
+                    return Err(CliError::Message(
+                        "Use --force to remove a key that is currently in use".to_string(),
+                    ));
+                }
             }
         }
 
-        match store.remove_key(&args.key_name) {
+        match store.remove_key(&key_name) {
             Some(_) => {
                 store.save().map_err(CliError::SaveFailed)?;
 
-                println!("Successfully removed key '{}'", &args.key_name);
+                log::info!("Successfully removed key '{}'", &key_name);
 
                 Ok(())
             }
 
-            None => Err(CliError::KeyNotFound(args.key_name.clone())),
+            None => Err(CliError::KeyNotFound(key_name)),
+        }
+    }
+
+    pub fn handle_config(&self, args: &ConfigArgs) -> SubcommandResult {
+        match &args.command {
+            ConfigCommand::Get(args) => self.handle_config_get(args),
+            ConfigCommand::Set(args) => self.handle_config_set(args),
         }
     }
 
+    pub fn handle_config_get(&self, args: &ConfigGetArgs) -> SubcommandResult {
+        let value = config::load()
+            .get_field(&args.field)
+            .map_err(CliError::Message)?;
+
+        println!("{value}");
+        Ok(())
+    }
+
+    pub fn handle_config_set(&self, args: &ConfigSetArgs) -> SubcommandResult {
+        let mut current = config::load();
+        current
+            .set_field(&args.field, &args.value)
+            .map_err(CliError::Message)?;
+
+        config::save(&current).map_err(CliError::SaveFailed)?;
+
+        log::info!("Set '{}' to '{}'", args.field, args.value);
+        Ok(())
+    }
+
     pub fn handle(&self) -> Result<(), Box<dyn Error>> {
         let mut command = Self::command();
 
@@ -246,18 +779,24 @@ impl KeyManCli {
         let mut store = SshKeyStorage::from_default_file().unwrap_or_default();
 
         let result: SubcommandResult = match subcommand {
-            Command::List => self.handle_list(&store),
+            Command::List(args) => self.handle_list(&store, args.format),
             Command::Add(args) => self.handle_add(args, &mut store),
-            Command::Use { key_name } => self.handle_use(key_name, &mut store),
+            Command::Generate(args) => self.handle_generate(args, &mut store),
+            Command::Use { key_name } => self.handle_use(key_name.as_deref(), &mut store),
             Command::Rename(args) => self.handle_rename(args, &mut store),
             Command::Remove(args) => self.handle_remove(args, &mut store),
-            Command::Info { key_name } => self.handle_info(key_name.as_deref(), &store),
+            Command::Renew(args) => self.handle_renew(args, &mut store),
+            Command::Host(args) => self.handle_host(args, &mut store),
+            Command::Info { key_name, format } => {
+                self.handle_info(key_name.as_deref(), &store, *format)
+            }
+            Command::Config(args) => self.handle_config(args),
         };
 
         match result {
             Ok(_) => Ok(()),
             Err(err) => {
-                eprintln!("{}", err.to_string());
+                log::error!("{}", err.to_string());
                 std::process::exit(1);
             }
         }
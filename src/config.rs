@@ -0,0 +1,118 @@
+use std::{env, error::Error, fs, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::store;
+
+pub const CONFIG_FILE: &str = "config.toml";
+
+/// Layered defaults for keyman: a `config.toml` under [`store::get_folder()`], with sane
+/// built-in defaults when the file is absent and `KEYMAN_*` environment variables layered on
+/// top, the way cargo layers its own config.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Config {
+    #[serde(default = "default_key_algorithm")]
+    pub default_key_algorithm: String,
+
+    #[serde(default = "default_identity_file")]
+    pub default_identity_file: String,
+
+    /// Overrides the `~/.ssh` folder when set.
+    #[serde(default)]
+    pub ssh_dir: Option<PathBuf>,
+
+    #[serde(default = "default_true")]
+    pub auto_derive_public_key: bool,
+}
+
+fn default_key_algorithm() -> String {
+    "ed25519".to_string()
+}
+
+fn default_identity_file() -> String {
+    store::DEFAULT_SSH_KEY_NAME.to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            default_key_algorithm: default_key_algorithm(),
+            default_identity_file: default_identity_file(),
+            ssh_dir: None,
+            auto_derive_public_key: true,
+        }
+    }
+}
+
+impl Config {
+    pub fn get_field(&self, field: &str) -> Result<String, String> {
+        match field {
+            "default-key-algorithm" => Ok(self.default_key_algorithm.clone()),
+            "default-identity-file" => Ok(self.default_identity_file.clone()),
+            "ssh-dir" => Ok(self
+                .ssh_dir
+                .as_ref()
+                .map_or("(default)".to_string(), |p| p.to_string_lossy().to_string())),
+            "auto-derive-public-key" => Ok(self.auto_derive_public_key.to_string()),
+            _ => Err(format!("unknown config field '{field}'")),
+        }
+    }
+
+    pub fn set_field(&mut self, field: &str, value: &str) -> Result<(), String> {
+        match field {
+            "default-key-algorithm" => self.default_key_algorithm = value.to_string(),
+            "default-identity-file" => self.default_identity_file = value.to_string(),
+            "ssh-dir" => self.ssh_dir = Some(PathBuf::from(value)),
+            "auto-derive-public-key" => {
+                self.auto_derive_public_key = value
+                    .parse()
+                    .map_err(|_| "expected 'true' or 'false'".to_string())?;
+            }
+            _ => return Err(format!("unknown config field '{field}'")),
+        }
+
+        Ok(())
+    }
+}
+
+fn apply_env_overrides(config: &mut Config) {
+    if let Ok(dir) = env::var("KEYMAN_SSH_DIR") {
+        config.ssh_dir = Some(PathBuf::from(dir));
+    }
+
+    if let Ok(name) = env::var("KEYMAN_IDENTITY_FILE") {
+        config.default_identity_file = name;
+    }
+
+    if let Ok(algorithm) = env::var("KEYMAN_KEY_ALGORITHM") {
+        config.default_key_algorithm = algorithm;
+    }
+}
+
+/// Loads `config.toml`, falling back to defaults when it doesn't exist or fails to parse, then
+/// layers any `KEYMAN_*` environment variable overrides on top.
+pub fn load() -> Config {
+    let path = store::get_folder().join(CONFIG_FILE);
+
+    let mut config = fs::read_to_string(&path)
+        .ok()
+        .and_then(|contents| toml::from_str(&contents).ok())
+        .unwrap_or_default();
+
+    apply_env_overrides(&mut config);
+    config
+}
+
+pub fn save(config: &Config) -> Result<(), Box<dyn Error>> {
+    store::create_folders()?;
+
+    let path = store::get_folder().join(CONFIG_FILE);
+    fs::write(path, toml::to_string_pretty(config)?)?;
+
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+    sync::Mutex,
+};
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+use crate::store;
+
+/// A small env-logger-style backend: prints level-prefixed lines to stdout/stderr and, once any
+/// verbosity is requested, mirrors them to `get_folder()/keyman.log` so a silent failure still
+/// leaves a breadcrumb trail.
+struct KeyManLogger {
+    level: LevelFilter,
+    file: Option<Mutex<File>>,
+}
+
+impl Log for KeyManLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let line = format!("[{:<5}] {}", record.level(), record.args());
+
+        match record.level() {
+            Level::Error => eprintln!("{line}"),
+            _ => println!("{line}"),
+        }
+
+        if let Some(file) = &self.file {
+            if let Ok(mut file) = file.lock() {
+                let _ = writeln!(file, "{line}");
+            }
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(file) = &self.file {
+            if let Ok(mut file) = file.lock() {
+                let _ = file.flush();
+            }
+        }
+    }
+}
+
+/// Sets up the global logger from the CLI's `-v`/`-q` flags. `-v` traces filesystem mutations
+/// at debug level and starts mirroring output to `keyman.log`; `-vv` goes further and traces
+/// every path operation. `--quiet` drops everything but errors.
+pub fn init(verbosity: u8, quiet: bool) {
+    let level = if quiet {
+        LevelFilter::Error
+    } else {
+        match verbosity {
+            0 => LevelFilter::Info,
+            1 => LevelFilter::Debug,
+            _ => LevelFilter::Trace,
+        }
+    };
+
+    let file = (verbosity > 0).then(open_log_file).flatten();
+
+    log::set_max_level(level);
+    let _ = log::set_boxed_logger(Box::new(KeyManLogger { level, file }));
+}
+
+fn open_log_file() -> Option<Mutex<File>> {
+    store::create_folders().ok()?;
+
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(store::get_folder().join("keyman.log"))
+        .ok()
+        .map(Mutex::new)
+}
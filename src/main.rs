@@ -2,19 +2,23 @@ use clap::Parser;
 use cli::KeyManCli;
 
 mod cli;
+mod config;
 mod error;
+mod logging;
 mod platform;
 mod store;
 
 fn main() {
     let cli = KeyManCli::parse();
 
+    logging::init(cli.verbose, cli.quiet);
+
     match cli.handle() {
         Ok(_) => (),
 
         // incase any other error occurs that isn't from a subcommand
         Err(err) => {
-            eprintln!("Something went wrong: {}", err);
+            log::error!("Something went wrong: {}", err);
             std::process::exit(1);
         }
     }
@@ -11,14 +11,23 @@ pub fn get_home_folder() -> PathBuf {
 }
 
 pub fn get_ssh_path() -> PathBuf {
-    get_home_folder().join(".ssh")
+    crate::config::load()
+        .ssh_dir
+        .unwrap_or_else(|| get_home_folder().join(".ssh"))
 }
 
 pub fn soft_link(from: &PathBuf, to: &PathBuf) -> Result<(), io::Error> {
     if to.is_symlink() {
+        log::debug!("replacing existing symlink at {}", to.to_string_lossy());
         fs::remove_file(to)?;
     }
 
+    log::trace!(
+        "symlinking {} -> {}",
+        to.to_string_lossy(),
+        from.to_string_lossy()
+    );
+
     #[cfg(target_family = "windows")]
     os::windows::fs::symlink_file(from, to)?;
 
@@ -1,14 +1,183 @@
-use std::{collections::HashMap, error::Error, fs, io, path::PathBuf};
+use std::{collections::HashMap, env, error::Error, fs, io, path::PathBuf};
 
+use rand_core::OsRng;
 use serde::{Deserialize, Serialize};
+use ssh_key::{Algorithm, EcdsaCurve, HashAlg, LineEnding, PrivateKey, PublicKey, private::RsaKeypair};
 
 use crate::platform;
 
-// todo: make configurable
 pub const DEFAULT_SSH_KEY_NAME: &str = "id_rsa";
 
 pub const DEFAULT_JSON_FILE: &str = "keys.json";
 
+pub const DEFAULT_RSA_BITS: usize = 4096;
+
+const CONFIG_BLOCK_BEGIN: &str = "# BEGIN keyman";
+const CONFIG_BLOCK_END: &str = "# END keyman";
+
+/// Splices `block` into `existing` between the `keyman` markers, replacing a previous managed
+/// block if one is present and leaving everything else untouched.
+fn replace_managed_block(existing: &str, block: &str) -> String {
+    let managed = format!("{CONFIG_BLOCK_BEGIN}\n{block}{CONFIG_BLOCK_END}\n");
+
+    match (
+        existing.find(CONFIG_BLOCK_BEGIN),
+        existing.find(CONFIG_BLOCK_END),
+    ) {
+        (Some(start), Some(end)) => {
+            let end = end + CONFIG_BLOCK_END.len();
+            // `managed` already ends with a newline, so drop the old block's own
+            let rest = &existing[end..];
+            let rest = rest.strip_prefix('\n').unwrap_or(rest);
+            format!("{}{managed}{rest}", &existing[..start])
+        }
+
+        _ if existing.trim().is_empty() => managed,
+        _ => format!("{}\n\n{managed}", existing.trim_end()),
+    }
+}
+
+/// The algorithm to generate a new keypair with, exposed to the CLI via `clap::ValueEnum`.
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum KeyType {
+    Ed25519,
+    Rsa,
+    P256,
+    P384,
+}
+
+fn default_comment() -> String {
+    let user = env::var("USER")
+        .or_else(|_| env::var("USERNAME"))
+        .unwrap_or_else(|_| "user".to_string());
+
+    let host = env::var("HOSTNAME").unwrap_or_else(|_| "host".to_string());
+
+    format!("{user}@{host}")
+}
+
+#[cfg(unix)]
+fn set_permissions(path: &PathBuf, mode: u32) -> Result<(), io::Error> {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))
+}
+
+#[cfg(not(unix))]
+fn set_permissions(_path: &PathBuf, _mode: u32) -> Result<(), io::Error> {
+    Ok(())
+}
+
+/// Generates a fresh keypair of the given type in memory, optionally encrypted with a
+/// passphrase. Shared by `generate_key` and `renew_key`.
+fn build_keypair(
+    key_type: KeyType,
+    bits: usize,
+    comment: Option<String>,
+    passphrase: Option<&str>,
+) -> Result<PrivateKey, Box<dyn Error>> {
+    let algorithm = match key_type {
+        KeyType::Ed25519 => Algorithm::Ed25519,
+        KeyType::Rsa => Algorithm::Rsa { hash: None },
+        KeyType::P256 => Algorithm::Ecdsa {
+            curve: EcdsaCurve::NistP256,
+        },
+        KeyType::P384 => Algorithm::Ecdsa {
+            curve: EcdsaCurve::NistP384,
+        },
+    };
+
+    let mut private_key = if let KeyType::Rsa = key_type {
+        let keypair = RsaKeypair::random(&mut OsRng, bits)?;
+        PrivateKey::new(keypair.into(), "")?
+    } else {
+        PrivateKey::random(&mut OsRng, algorithm)?
+    };
+
+    private_key.set_comment(comment.unwrap_or_else(default_comment));
+
+    if let Some(passphrase) = passphrase {
+        private_key = private_key.encrypt(&mut OsRng, passphrase)?;
+    }
+
+    Ok(private_key)
+}
+
+/// Writes a generated keypair to disk, setting `0o600`/`0o644` permissions on unix.
+fn write_keypair(
+    private_key: &PrivateKey,
+    private_key_path: &PathBuf,
+    public_key_path: &PathBuf,
+) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = private_key_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    log::trace!("writing private key to {}", private_key_path.to_string_lossy());
+    fs::write(private_key_path, private_key.to_openssh(LineEnding::LF)?)?;
+
+    log::trace!("writing public key to {}", public_key_path.to_string_lossy());
+    fs::write(public_key_path, private_key.public_key().to_openssh()?)?;
+
+    set_permissions(private_key_path, 0o600)?;
+    set_permissions(public_key_path, 0o644)?;
+
+    Ok(())
+}
+
+/// The bit-length of an RSA modulus stored as an `Mpint`, which big-endian-pads with a leading
+/// zero byte whenever the high bit of the most significant byte is set (so `as_bytes().len() *
+/// 8` over-counts by a byte for any modulus whose bit-length is a multiple of 8, such as every
+/// 2048- or 4096-bit key).
+fn mpint_bit_length(bytes: &[u8]) -> usize {
+    let mut significant = bytes.iter().skip_while(|&&b| b == 0);
+
+    match significant.next() {
+        Some(&first) => (8 - first.leading_zeros() as usize) + significant.count() * 8,
+        None => 0,
+    }
+}
+
+/// Maps a parsed public key back to the `KeyType`/bit-size that would regenerate an equivalent
+/// keypair, used by `renew_key` to preserve a key's identity across rotation. Algorithms without
+/// a matching `KeyType` (e.g. P-521) are an error.
+fn algorithm_to_key_type_and_bits(
+    public_key: &ssh_key::PublicKey,
+) -> Result<(KeyType, usize), Box<dyn Error>> {
+    match public_key.algorithm() {
+        Algorithm::Rsa { .. } => {
+            let bits = public_key
+                .key_data()
+                .rsa()
+                .map_or(DEFAULT_RSA_BITS, |rsa| mpint_bit_length(rsa.n.as_bytes()));
+
+            Ok((KeyType::Rsa, bits))
+        }
+
+        Algorithm::Ecdsa {
+            curve: EcdsaCurve::NistP256,
+        } => Ok((KeyType::P256, 0)),
+
+        Algorithm::Ecdsa {
+            curve: EcdsaCurve::NistP384,
+        } => Ok((KeyType::P384, 0)),
+
+        Algorithm::Ed25519 => Ok((KeyType::Ed25519, 0)),
+
+        other => Err(format!("renewing a {other} key is not supported").into()),
+    }
+}
+
+/// A coarse, sortable timestamp (seconds since the epoch) used to name archive folders.
+fn timestamp_string() -> String {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+        .to_string()
+}
+
 pub fn get_folder() -> PathBuf {
     let mut home_folder = platform::get_home_folder();
     home_folder.push(format!(".{}", env!("CARGO_PKG_NAME")));
@@ -23,11 +192,13 @@ pub fn get_keys_folder() -> PathBuf {
 pub fn create_folders() -> Result<(), io::Error> {
     let folder = get_folder();
     if !folder.exists() {
+        log::debug!("creating folder {}", folder.to_string_lossy());
         fs::create_dir_all(&folder)?;
     }
 
     let keys_folder = get_keys_folder();
     if !keys_folder.exists() {
+        log::debug!("creating folder {}", keys_folder.to_string_lossy());
         fs::create_dir_all(&keys_folder)?;
     }
 
@@ -122,6 +293,7 @@ impl SshKeyStorage {
         &mut self,
         path_to_key: PathBuf,
         name: Option<&str>,
+        passphrase: Option<String>,
     ) -> Result<&Key, Box<dyn Error>> {
         if !path_to_key.is_file() {
             return Err("invalid path to private key".into());
@@ -144,8 +316,44 @@ impl SshKeyStorage {
         let key = Key {
             original_path: Some(path_to_key),
             private_key_path: Some(store_path),
-            public_key_path: None,
+            pending_passphrase: passphrase,
+            name: key_name.clone(),
+            ..Default::default()
+        };
+
+        self.keys_by_name.insert(key_name.clone(), key);
+
+        Ok(self
+            .keys_by_name
+            .get(&key_name)
+            .expect("key was just added"))
+    }
+
+    pub fn generate_key(
+        &mut self,
+        key_type: KeyType,
+        bits: usize,
+        name: Option<&str>,
+        comment: Option<String>,
+        passphrase: Option<&str>,
+    ) -> Result<&Key, Box<dyn Error>> {
+        let key_name = name.map_or(self.default_next_name(), ToString::to_string);
+
+        if self.keys_by_name.contains_key(&key_name) {
+            return Err("key with that name already exists".into());
+        }
+
+        let private_key = build_keypair(key_type, bits, comment, passphrase)?;
+
+        let private_key_path = get_keys_folder().join(&key_name);
+        let public_key_path = private_key_path.with_extension("pub");
+        write_keypair(&private_key, &private_key_path, &public_key_path)?;
+
+        let key = Key {
+            private_key_path: Some(private_key_path),
+            public_key_path: Some(public_key_path),
             name: key_name.clone(),
+            ..Default::default()
         };
 
         self.keys_by_name.insert(key_name.clone(), key);
@@ -156,6 +364,85 @@ impl SshKeyStorage {
             .expect("key was just added"))
     }
 
+    pub fn renew_key(&mut self, name: &str, passphrase: Option<String>) -> Result<&Key, Box<dyn Error>> {
+        let key = self.keys_by_name.get(name).ok_or("no key with that name")?;
+
+        let private_key_path = key
+            .private_key_path
+            .clone()
+            .ok_or("key has no stored private key to renew")?;
+
+        let public_key_path = key
+            .public_key_path
+            .clone()
+            .unwrap_or_else(|| private_key_path.with_extension("pub"));
+
+        // the public key component of an OpenSSH private key file is stored unencrypted even
+        // when the private key material is passphrase-protected, so read it directly rather
+        // than relying on a derived `.pub` file that may not exist
+        let contents = fs::read_to_string(&private_key_path)?;
+        let existing_private_key = PrivateKey::from_openssh(&contents)?;
+
+        let (key_type, bits) = algorithm_to_key_type_and_bits(existing_private_key.public_key())?;
+        let comment = existing_private_key.comment();
+        let comment = (!comment.is_empty()).then(|| comment.to_string());
+
+        let new_passphrase = if existing_private_key.is_encrypted() {
+            use std::io::IsTerminal;
+
+            match passphrase.or_else(|| key.pending_passphrase.clone()) {
+                Some(passphrase) => Some(passphrase),
+                None if io::stdin().is_terminal() => Some(rpassword::prompt_password(format!(
+                    "Passphrase for '{}' (reused to encrypt the renewed key): ",
+                    key.name
+                ))?),
+                None => {
+                    return Err("key is passphrase-protected; supply --passphrase to renew it \
+                                without dropping that protection"
+                        .into());
+                }
+            }
+        } else {
+            None
+        };
+
+        // the old key must decrypt with the passphrase before it is reused for the new key
+        if let Some(ref passphrase) = new_passphrase {
+            existing_private_key.decrypt(passphrase)?;
+        }
+
+        // archive the old files rather than deleting them, so a mistaken renewal is recoverable
+        let archive_folder = get_keys_folder().join("archive").join(timestamp_string());
+        fs::create_dir_all(&archive_folder)?;
+
+        if private_key_path.exists() {
+            fs::rename(&private_key_path, archive_folder.join(&key.name))?;
+        }
+
+        if public_key_path.exists() {
+            fs::rename(
+                &public_key_path,
+                archive_folder.join(format!("{}.pub", key.name)),
+            )?;
+        }
+
+        let new_private_key = build_keypair(key_type, bits, comment, new_passphrase.as_deref())?;
+        write_keypair(&new_private_key, &private_key_path, &public_key_path)?;
+
+        let key = self.keys_by_name.get_mut(name).expect("key must exist");
+        key.original_path = None;
+        key.private_key_path = Some(private_key_path);
+        key.public_key_path = Some(public_key_path);
+        key.pending_passphrase = None;
+
+        let key = self.keys_by_name.get(name).expect("key must exist");
+        if self.active_key_name.as_deref() == Some(name) {
+            key.link()?;
+        }
+
+        Ok(key)
+    }
+
     pub fn remove_key(&mut self, name: &str) -> Option<&Key> {
         let key = match self.keys_by_name.remove(name) {
             Some(key) => key,
@@ -174,6 +461,93 @@ impl SshKeyStorage {
         self.marked_for_deletion.last()
     }
 
+    pub fn add_host(
+        &mut self,
+        key_name: &str,
+        user: &str,
+        hostname: &str,
+        port: Option<u16>,
+    ) -> Result<&Host, Box<dyn Error>> {
+        let key = self
+            .keys_by_name
+            .get_mut(key_name)
+            .ok_or("no key with that name")?;
+
+        if key.hosts.iter().any(|h| h.id == hostname) {
+            return Err("this key is already associated with that host".into());
+        }
+
+        key.hosts.push(Host {
+            id: hostname.to_string(),
+            user: user.to_string(),
+            hostname: hostname.to_string(),
+            port,
+        });
+
+        Ok(key.hosts.last().expect("host was just added"))
+    }
+
+    pub fn remove_host(&mut self, key_name: &str, host_id: &str) -> Option<Host> {
+        let key = self.keys_by_name.get_mut(key_name)?;
+        let index = key.hosts.iter().position(|h| h.id == host_id)?;
+
+        Some(key.hosts.remove(index))
+    }
+
+    /// Rewrites the `# BEGIN keyman` / `# END keyman` managed block in `~/.ssh/config` so that
+    /// every key with associated hosts gets a `Host` entry pointing at its stored private key.
+    /// Hand-written entries outside the markers are left untouched.
+    fn write_ssh_config(&self) -> Result<(), Box<dyn Error>> {
+        let config_path = platform::get_ssh_path().join("config");
+        let existing = fs::read_to_string(&config_path).unwrap_or_default();
+
+        let block = self.render_managed_block();
+        if block.is_empty() && !existing.contains(CONFIG_BLOCK_BEGIN) {
+            return Ok(()); // nothing to do, and nothing to clean up
+        }
+
+        if let Some(parent) = config_path.parent() {
+            if !parent.exists() {
+                fs::create_dir_all(parent)?;
+            }
+        }
+
+        fs::write(&config_path, replace_managed_block(&existing, &block))?;
+        Ok(())
+    }
+
+    fn render_managed_block(&self) -> String {
+        let mut block = String::new();
+
+        // keys_by_name is a HashMap, so iteration order is randomized per-run; sort by name for
+        // a stable block order and less diff churn for anyone tracking ~/.ssh/config
+        let mut keys: Vec<&Key> = self.keys_by_name.values().collect();
+        keys.sort_by(|a, b| a.name.cmp(&b.name));
+
+        for key in keys {
+            let Some(ref private_key_path) = key.private_key_path else {
+                continue;
+            };
+
+            for host in &key.hosts {
+                block.push_str(&format!("Host {}\n", host.id));
+                block.push_str(&format!("    HostName {}\n", host.hostname));
+                block.push_str(&format!("    User {}\n", host.user));
+
+                if let Some(port) = host.port {
+                    block.push_str(&format!("    Port {port}\n"));
+                }
+
+                block.push_str(&format!(
+                    "    IdentityFile {}\n\n",
+                    private_key_path.to_string_lossy()
+                ));
+            }
+        }
+
+        block
+    }
+
     pub fn rename_key(&mut self, name: &str, new_name: &str) -> Option<&Key> {
         if !self.keys_by_name.contains_key(name) {
             return None;
@@ -202,7 +576,7 @@ impl SshKeyStorage {
         serde_json::to_writer_pretty(file, &self)?;
 
         // save all of the keys
-        for key in self.keys_by_name.values() {
+        for key in self.keys_by_name.values_mut() {
             key.save()?;
         }
 
@@ -211,6 +585,7 @@ impl SshKeyStorage {
         }
 
         self.marked_for_deletion.clear();
+        self.write_ssh_config()?;
 
         Ok(output_path)
     }
@@ -227,16 +602,89 @@ pub struct Key {
     pub public_key_path: Option<PathBuf>,
 
     pub name: String,
+
+    /// Passphrase supplied at import time, consumed by `derive_public_key` on the first save
+    /// and never persisted to disk.
+    #[serde(default, skip)]
+    pending_passphrase: Option<String>,
+
+    /// Hosts this key should be used for via a managed `~/.ssh/config` entry, rather than the
+    /// single global symlink.
+    #[serde(default)]
+    pub hosts: Vec<Host>,
+}
+
+/// A stable, serde-friendly view of a [`Key`] for `--format json` output. Unlike `Key`
+/// itself, this always carries the computed algorithm and fingerprint rather than requiring the
+/// caller to derive them.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyView {
+    pub name: String,
+    pub private_key_path: Option<PathBuf>,
+    pub public_key_path: Option<PathBuf>,
+    pub algorithm: Option<String>,
+    pub fingerprint: Option<String>,
+    pub in_use: bool,
+    pub hosts: Vec<Host>,
+}
+
+/// A `Host` entry in `~/.ssh/config` associated with a [`Key`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Host {
+    /// The `Host` alias written to the config file, currently always the hostname.
+    pub id: String,
+    pub user: String,
+    pub hostname: String,
+    pub port: Option<u16>,
 }
 
 impl Key {
+    /// Parses the stored public key file, if any, for display purposes (algorithm,
+    /// fingerprint). Returns `None` rather than erroring if it can't be read or parsed.
+    fn parse_public_key(&self) -> Option<PublicKey> {
+        let path = self.public_key_path.as_ref()?;
+        let contents = fs::read_to_string(path).ok()?;
+
+        PublicKey::from_openssh(&contents).ok()
+    }
+
+    pub fn algorithm_name(&self) -> Option<String> {
+        self.parse_public_key().map(|key| key.algorithm().to_string())
+    }
+
+    pub fn fingerprint(&self) -> Option<String> {
+        self.parse_public_key()
+            .map(|key| key.fingerprint(HashAlg::Sha256).to_string())
+    }
+
+    /// Builds the stable, serde-friendly view of this key used by `--format json`.
+    pub fn to_view(&self, in_use: bool) -> KeyView {
+        KeyView {
+            name: self.name.clone(),
+            private_key_path: self.private_key_path.clone(),
+            public_key_path: self.public_key_path.clone(),
+            algorithm: self.algorithm_name(),
+            fingerprint: self.fingerprint(),
+            in_use,
+            hosts: self.hosts.clone(),
+        }
+    }
+
     pub fn link(&self) -> Result<(), io::Error> {
         if self.private_key_path.as_ref().is_none() {
             return Ok(()); // nothing to link
         }
 
+        // host-specific keys are reached through their managed `~/.ssh/config` entry instead
+        // of the global symlink
+        if !self.hosts.is_empty() {
+            return Ok(());
+        }
+
         let ssh_path = platform::get_ssh_path();
-        let key_link_to = ssh_path.join(DEFAULT_SSH_KEY_NAME);
+        let key_link_to = ssh_path.join(crate::config::load().default_identity_file);
 
         platform::soft_link(self.private_key_path.as_ref().unwrap(), &key_link_to)?;
 
@@ -245,38 +693,89 @@ impl Key {
 
     pub fn delete(&self) -> Result<(), io::Error> {
         if let Some(path) = self.private_key_path.as_ref() {
+            log::debug!("deleting {}", path.to_string_lossy());
             fs::remove_file(path)?;
         }
 
         if let Some(path) = self.public_key_path.as_ref() {
+            log::debug!("deleting {}", path.to_string_lossy());
             fs::remove_file(path)?;
         }
 
         Ok(())
     }
 
-    pub fn save(&self) -> Result<(), Box<dyn Error>> {
-        // if the private key already exists, we don't need to save it again
-        if self.private_key_path.as_ref().is_some_and(|p| p.exists()) {
-            return Ok(());
-        }
+    pub fn save(&mut self) -> Result<(), Box<dyn Error>> {
+        // if the private key already exists, we don't need to copy it again
+        let already_stored = self.private_key_path.as_ref().is_some_and(|p| p.exists());
 
-        // we can't copy to an empty path or if the original file doesn't exist
-        if self.private_key_path.is_none()
-            || self.original_path.as_ref().is_none_or(|p| !p.exists())
-        {
-            return Err("No private key path or the original file doesn't exist".into());
+        if !already_stored {
+            // we can't copy to an empty path or if the original file doesn't exist
+            if self.private_key_path.is_none()
+                || self.original_path.as_ref().is_none_or(|p| !p.exists())
+            {
+                return Err("No private key path or the original file doesn't exist".into());
+            }
+
+            let original_path = self.original_path.as_ref().unwrap();
+            let private_key_path = self.private_key_path.as_ref().unwrap();
+
+            let save_to_folder = private_key_path.parent();
+            if save_to_folder.as_ref().is_some_and(|p| !p.exists()) {
+                fs::create_dir_all(save_to_folder.unwrap())?;
+            }
+
+            log::debug!(
+                "copying {} to {}",
+                original_path.to_string_lossy(),
+                private_key_path.to_string_lossy()
+            );
+            fs::copy(original_path, private_key_path)?;
+
+            // only attempt this right after the key is first copied into the store, not on
+            // every subsequent save() of every other key - otherwise a key whose passphrase was
+            // declined (or skipped non-interactively) at import time gets re-prompted for on
+            // every unrelated mutating command
+            if self.public_key_path.is_none() && crate::config::load().auto_derive_public_key {
+                if let Err(err) = self.derive_public_key() {
+                    log::warn!("Could not derive a public key for '{}': {}", self.name, err);
+                }
+            }
         }
 
-        let original_path = self.original_path.as_ref().unwrap();
-        let private_key_path = self.private_key_path.as_ref().unwrap();
+        Ok(())
+    }
+
+    /// Parses the stored private key, writes the corresponding `.pub` file alongside it and
+    /// records `public_key_path`. An encrypted key is decrypted with the import-time passphrase,
+    /// or one prompted for when stdin is a TTY; otherwise its public key is left unset.
+    fn derive_public_key(&mut self) -> Result<(), Box<dyn Error>> {
+        let private_key_path = match self.private_key_path.as_ref() {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        let contents = fs::read_to_string(private_key_path)?;
+        let mut private_key = ssh_key::PrivateKey::from_openssh(&contents)?;
+
+        if private_key.is_encrypted() {
+            use std::io::IsTerminal;
+
+            let passphrase = match self.pending_passphrase.take() {
+                Some(passphrase) => passphrase,
+                None if io::stdin().is_terminal() => {
+                    rpassword::prompt_password(format!("Passphrase for '{}': ", self.name))?
+                }
+                // can't prompt non-interactively, leave the public key unset for now
+                None => return Ok(()),
+            };
 
-        let save_to_folder = private_key_path.parent();
-        if save_to_folder.as_ref().is_some_and(|p| !p.exists()) {
-            fs::create_dir_all(save_to_folder.unwrap())?;
+            private_key = private_key.decrypt(&passphrase)?;
         }
 
-        fs::copy(original_path, private_key_path)?;
+        let public_key_path = private_key_path.with_extension("pub");
+        fs::write(&public_key_path, private_key.public_key().to_openssh()?)?;
+        self.public_key_path = Some(public_key_path);
 
         Ok(())
     }